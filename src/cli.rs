@@ -0,0 +1,202 @@
+use name_similarity::{
+    check_extensions, default_extension_aliases, find_content_duplicates, find_similar,
+    load_extension_aliases, ScanConfig,
+};
+use opentelemetry::global;
+use tracing::{info, instrument};
+use tracing_subscriber::{prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt};
+
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+/// How duplicates are detected.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Compare tokenised file names with the cosine similarity heuristic
+    Name,
+    /// Compare file contents with a two-phase size/partial/full hash
+    Content,
+}
+
+/// How results are rendered to stdout.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Output {
+    /// Human-readable tracing spans plus a count (the default)
+    Text,
+    /// A single JSON object with a `matches` array (`mismatches` with
+    /// `--check-extensions`) and a `summary`
+    Json,
+    /// One JSON object per line, written after all results are collected
+    Ndjson,
+}
+
+/// Generate all-file name similarity
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Start point for file-name locating
+    root: Vec<String>,
+
+    /// Minimum similarity to consider a match
+    #[arg(short, long, default_value_t = 0.6)]
+    threshold: f32,
+
+    /// Reverse display direction of results
+    #[arg(short, long, default_value_t = false)]
+    reverse: bool,
+
+    /// Length of n-word tuple to use as basis vector components
+    #[arg(short = 'l', long, default_value_t = 2)]
+    trie_len: usize,
+
+    /// File-names must match this pattern
+    #[arg(short, long, default_value_t = String::from(".*"))]
+    filename_pattern: String,
+
+    /// Duplicate-detection strategy to use
+    #[arg(long, value_enum, default_value_t = Mode::Name)]
+    mode: Mode,
+
+    /// Shorthand for `--mode content`: find byte-identical files
+    #[arg(long, default_value_t = false)]
+    hash: bool,
+
+    /// Size of the rayon thread pool (0 = one thread per logical core)
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+
+    /// Result output format
+    #[arg(short, long, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    /// Glob of directories/paths to skip (repeatable)
+    #[arg(long)]
+    ignore: Vec<String>,
+
+    /// Respect .gitignore/.ignore files found during the walk
+    #[arg(long, default_value_t = false)]
+    use_gitignore: bool,
+
+    /// Report files whose extension disagrees with their content type
+    #[arg(long, default_value_t = false)]
+    check_extensions: bool,
+
+    /// File of extra interchangeable extension pairs to allow (one pair per line)
+    #[arg(long)]
+    extension_aliases: Option<String>,
+
+    /// Only emit matches involving this file or subdirectory
+    #[arg(long)]
+    limit: Option<PathBuf>,
+}
+
+#[instrument]
+fn run(args: Args) {
+    if args.threads > 0 {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(args.threads)
+            .build_global()
+        {
+            tracing::debug!(error = debug(&e), "could not size rayon pool");
+        }
+    }
+
+    let config = ScanConfig {
+        roots: args.root,
+        threshold: args.threshold,
+        trie_len: args.trie_len,
+        filename_pattern: args.filename_pattern,
+        reverse: args.reverse,
+        ignore: args.ignore,
+        use_gitignore: args.use_gitignore,
+        limit: args.limit,
+    };
+
+    if args.check_extensions {
+        let mut aliases = default_extension_aliases();
+        if let Some(path) = &args.extension_aliases {
+            match load_extension_aliases(path) {
+                Ok(extra) => aliases.extend(extra),
+                Err(e) => tracing::warn!(path, error = debug(&e), "could not read alias file"),
+            }
+        }
+        let mismatches = check_extensions(&config, &aliases);
+        match args.output {
+            Output::Text => {
+                for m in &mismatches {
+                    info!(path = debug(&m.path), extension = debug(&m.extension), detected = m.detected, "mismatch");
+                }
+                info!(count = mismatches.len(), "total count");
+                println!("total count = {}", mismatches.len());
+            }
+            Output::Json => {
+                let report = serde_json::json!({
+                    "mismatches": mismatches,
+                    "summary": { "count": mismatches.len() },
+                });
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            }
+            Output::Ndjson => {
+                for m in &mismatches {
+                    println!("{}", serde_json::to_string(m).unwrap());
+                }
+            }
+        }
+        return;
+    }
+
+    let mode = if args.hash { Mode::Content } else { args.mode };
+    let duplicates = match mode {
+        Mode::Name => find_similar(&config),
+        Mode::Content => find_content_duplicates(&config),
+    };
+
+    match args.output {
+        Output::Text => {
+            for m in &duplicates {
+                info!(score = m.score, total = m.total_size, path_a = debug(&m.path_a), path_b = debug(&m.path_b), "result");
+            }
+            info!(count = duplicates.len(), "total count");
+            println!("total count = {}", duplicates.len());
+        }
+        Output::Json => {
+            let report = serde_json::json!({
+                "matches": duplicates,
+                "summary": { "count": duplicates.len() },
+            });
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+        Output::Ndjson => {
+            // One object per line; the whole result set is collected and
+            // ordered before the first line is written.
+            for m in &duplicates {
+                println!("{}", serde_json::to_string(m).unwrap());
+            }
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        // First, create a OTLP exporter builder. Configure it as you need.
+        let http_client = reqwest::blocking::Client::new();
+        let otlp_exporter = opentelemetry_otlp::new_exporter().http().with_http_client(http_client);
+        // Then pass it into pipeline builder
+        let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(otlp_exporter)
+                .install_simple()?;
+            let opentelemetry = tracing_opentelemetry::layer().with_tracer(tracer);
+            tracing_subscriber::registry()
+                .with(opentelemetry)
+                .try_init()?;
+    let args = Args::parse();
+    run(args);
+
+    // Shut down the current tracer provider. This will invoke the shutdown
+    // method on all span processors. span processors should export remaining
+    // spans before return.
+    global::shutdown_tracer_provider();
+
+    Ok(())
+}
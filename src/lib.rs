@@ -0,0 +1,583 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::Path;
+use siphasher::sip128::{Hasher128, SipHasher13};
+use tracing::{debug, info, instrument};
+use std::{collections::HashMap, collections::HashSet, hash::Hasher, io::Read, path::PathBuf};
+
+/// Block size used for both the partial (first-block) and streaming full hash.
+const BLOCK_SIZE: usize = 4096;
+
+/// Configuration for a single similarity scan.
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    /// Start points for file-name locating.
+    pub roots: Vec<String>,
+    /// Minimum similarity to consider a match.
+    pub threshold: f32,
+    /// Length of n-word tuple to use as basis vector components.
+    pub trie_len: usize,
+    /// File-names must match this pattern.
+    pub filename_pattern: String,
+    /// Reverse display direction of results.
+    pub reverse: bool,
+    /// Directory/path globs whose subtrees are never descended into.
+    pub ignore: Vec<String>,
+    /// Honor `.gitignore`/`.ignore` files encountered during the walk.
+    pub use_gitignore: bool,
+    /// Only emit matches involving this file or subdirectory.
+    pub limit: Option<PathBuf>,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            roots: Vec::new(),
+            threshold: 0.6,
+            trie_len: 2,
+            filename_pattern: String::from(".*"),
+            reverse: false,
+            ignore: Vec::new(),
+            use_gitignore: false,
+            limit: None,
+        }
+    }
+}
+
+/// A resolved `--limit`: either a single file or a subtree to keep matches for.
+struct Limit {
+    path: PathBuf,
+    is_dir: bool,
+}
+
+impl Limit {
+    fn new(path: &Path) -> Self {
+        let is_dir = path.is_dir();
+        // Canonicalize so prefix/equality checks are independent of how the
+        // limit and the scanned roots were spelled on the command line.
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        Self { path, is_dir }
+    }
+
+    /// Whether `candidate` falls within the limit (equal file, or under the dir).
+    fn matches(&self, candidate: &Path) -> bool {
+        let candidate = candidate
+            .canonicalize()
+            .unwrap_or_else(|_| candidate.to_path_buf());
+        if self.is_dir {
+            candidate.starts_with(&self.path)
+        } else {
+            candidate == self.path
+        }
+    }
+}
+
+/// A single similar (or identical) pair of files.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Match {
+    /// Cosine similarity for name matches, `1.0` for content-identical files.
+    pub score: f32,
+    pub path_a: PathBuf,
+    pub path_b: PathBuf,
+    /// Combined size of both files, in bytes.
+    #[serde(rename = "combined_size")]
+    pub total_size: u64,
+    /// The n-gram tokens the two names have in common (empty for content matches).
+    pub tokens: Vec<String>,
+}
+
+/// An entry discovered during scanning: its path, size and tokenised name.
+pub type Entry = (PathBuf, u64, HashSet<String>);
+
+#[instrument]
+fn process_file(mut acc: Vec<Entry>, entry: ignore::DirEntry, filename_regex: &regex::Regex, trie_len: usize) -> Vec<Entry> {
+    if let Ok(meta) = entry.metadata() {
+        debug!(name = debug(entry.path()), "found");
+        if meta.is_file() && filename_regex.is_match(&entry.file_name().to_string_lossy()) {
+            let i = entry.file_name().to_string_lossy().to_string();
+            let i = i
+                .split(|c: char| !c.is_alphanumeric())
+                .map(|v| v.to_lowercase());
+            let parts: HashSet<String> = match trie_len {
+                1 => i.collect(),
+                2 => i
+                    .tuple_windows::<(_, _)>()
+                    .map(|(a, b)| a + "." + &b)
+                    .collect(),
+                3 => i
+                    .tuple_windows::<(_, _, _)>()
+                    .map(|(a, b, c)| a + "." + &b + "." + &c)
+                    .collect(),
+                4 => i
+                    .tuple_windows::<(_, _, _, _)>()
+                    .map(|(a, b, c, d)| a + "." + &b + "." + &c + "." + &d)
+                    .collect(),
+                _ => unreachable!(),
+            };
+            acc.push((
+                entry.path().to_owned(),
+                entry.metadata().map(|m| m.len()).unwrap_or_default(),
+                parts,
+            ));
+        }
+    }
+    acc
+}
+
+/// Compile the repeatable `--ignore` globs into a single matcher.
+fn build_globset(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => debug!(pattern, error = debug(&e), "ignoring invalid glob"),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+#[instrument]
+fn scan_dir(
+    root: &str,
+    pattern: &str,
+    trie_len: usize,
+    globs: &GlobSet,
+    use_gitignore: bool,
+) -> Vec<Entry> {
+    let filename_regex = regex::Regex::new(pattern).unwrap();
+    info!("Getting file listing from: {root}");
+
+    // The `ignore` crate's walker applies `.gitignore`/`.ignore` files per
+    // directory as it descends, so nested ignore files are honored natively.
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .hidden(false)
+        .parents(use_gitignore)
+        .ignore(use_gitignore)
+        .git_ignore(use_gitignore)
+        .git_exclude(use_gitignore)
+        .git_global(use_gitignore)
+        .require_git(false);
+
+    // Prune `--ignore` globs via `filter_entry` so matched subtrees are never
+    // descended into, not just filtered after the fact.
+    let glob_filter = globs.clone();
+    builder.filter_entry(move |entry| {
+        !(glob_filter.is_match(entry.path()) || glob_filter.is_match(entry.file_name()))
+    });
+
+    builder
+        .build()
+        .flatten()
+        .fold(Vec::new(), |acc, entry| process_file(acc, entry, &filename_regex, trie_len))
+}
+
+#[instrument(skip(limit))]
+fn calculate_duplicates(entries: &[Entry], threshold: f32, limit: Option<&Limit>) -> Vec<Match> {
+    info!("Generating similarity between {} entries", entries.len());
+
+    // Inverted index token -> entry indices, so each entry only has to be
+    // compared against candidates it actually shares at least one token with.
+    let mut index: HashMap<&String, Vec<usize>> = HashMap::new();
+    for (i, (_, _, words)) in entries.iter().enumerate() {
+        for word in words {
+            index.entry(word).or_default().push(i);
+        }
+    }
+
+    // Pre-resolve which entries fall within the `--limit` so pairs outside it
+    // are skipped before the intersection work, not just at print time.
+    let in_limit: Vec<bool> = entries
+        .iter()
+        .map(|(path, _, _)| limit.is_none_or(|l| l.matches(path)))
+        .collect();
+
+    // Outer loop over `i` runs in parallel; each worker builds a local Vec of
+    // matches that are flattened into the result.
+    (0..entries.len())
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let (path_a, size_a, words_a) = &entries[i];
+            let l1_sum = words_a.len();
+
+            // Gather the earlier entries sharing a token with this one.
+            let mut candidates: Vec<usize> = words_a
+                .iter()
+                .filter_map(|w| index.get(w))
+                .flatten()
+                .copied()
+                .filter(|&j| j < i)
+                .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+
+            // A pair is only worth scoring if at least one side is in the limit.
+            if limit.is_some() && !in_limit[i] {
+                candidates.retain(|&j| in_limit[j]);
+            }
+
+            let mut local = Vec::new();
+            for j in candidates {
+                let (path_b, size_b, words_b) = &entries[j];
+
+                // Intersect by walking the smaller set against the larger.
+                let (small, large) = if words_a.len() <= words_b.len() {
+                    (words_a, words_b)
+                } else {
+                    (words_b, words_a)
+                };
+                let shared: Vec<String> =
+                    small.iter().filter(|w| large.contains(*w)).cloned().collect();
+                let c = shared.len();
+
+                let l2_sum = words_b.len();
+                let cosine = (c as f32) / ((l1_sum * l2_sum) as f32).sqrt();
+
+                if cosine > threshold {
+                    debug!(path_a = debug(path_a), path_b = debug(path_b), cosine, "duplicate");
+                    local.push(Match {
+                        score: cosine,
+                        path_a: path_a.clone(),
+                        path_b: path_b.clone(),
+                        total_size: size_a + size_b,
+                        tokens: shared,
+                    });
+                }
+            }
+            local
+        })
+        .collect()
+}
+
+/// Per-entry scratch used while content hashing, so the partial and full hashes
+/// are each computed lazily and at most once.
+struct Hashed<'a> {
+    path: &'a PathBuf,
+    size: u64,
+    // `None` = not yet computed; the inner `Option` distinguishes a failed read
+    // (`Some(None)`) from a successful hash (`Some(Some(_))`).
+    partial: Option<Option<u128>>,
+    full: Option<Option<u128>>,
+}
+
+impl<'a> Hashed<'a> {
+    fn new(path: &'a PathBuf, size: u64) -> Self {
+        Self { path, size, partial: None, full: None }
+    }
+
+    /// Hash of the first [`BLOCK_SIZE`] bytes of the file, or `None` if unreadable.
+    fn partial(&mut self) -> Option<u128> {
+        if let Some(h) = self.partial {
+            return h;
+        }
+        let h = hash_blocks(self.path, true)
+            .map_err(|e| debug!(path = debug(self.path), error = debug(&e), "partial hash failed"))
+            .ok();
+        self.partial = Some(h);
+        h
+    }
+
+    /// Hash of the whole file streamed a block at a time, or `None` if unreadable.
+    fn full(&mut self) -> Option<u128> {
+        if let Some(h) = self.full {
+            return h;
+        }
+        let h = hash_blocks(self.path, false)
+            .map_err(|e| debug!(path = debug(self.path), error = debug(&e), "full hash failed"))
+            .ok();
+        self.full = Some(h);
+        h
+    }
+}
+
+/// Hash a file with siphash-1-3; when `partial` only the first block is read.
+fn hash_blocks(path: &PathBuf, partial: bool) -> std::io::Result<u128> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = SipHasher13::new();
+    let mut buf = [0u8; BLOCK_SIZE];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buf[..read]);
+        if partial {
+            break;
+        }
+    }
+    Ok(hasher.finish128().as_u128())
+}
+
+/// Detect byte-identical files using ddh's two-phase strategy: bucket by size,
+/// split each size bucket by a partial (first-block) hash, and only fall back to
+/// a full streaming hash for groups that still have more than one member.
+#[instrument]
+fn calculate_content_duplicates(entries: &[Entry]) -> Vec<Match> {
+    info!("Hashing {} entries for content duplicates", entries.len());
+
+    // Bucket by size; unique sizes can never collide and are dropped.
+    let mut by_size: HashMap<u64, Vec<Hashed>> = HashMap::new();
+    for (path, size, _) in entries {
+        by_size.entry(*size).or_default().push(Hashed::new(path, *size));
+    }
+
+    let mut duplicates = Vec::new();
+    for (_size, bucket) in by_size {
+        if bucket.len() < 2 {
+            continue;
+        }
+
+        // Split the size bucket by partial hash; drop entries we cannot read.
+        let mut by_partial: HashMap<u128, Vec<Hashed>> = HashMap::new();
+        for mut entry in bucket {
+            if let Some(partial) = entry.partial() {
+                by_partial.entry(partial).or_default().push(entry);
+            }
+        }
+
+        for (_partial, mut group) in by_partial {
+            if group.len() < 2 {
+                continue;
+            }
+
+            // Only now is the full hash worth computing; drop unreadable files.
+            let mut by_full: HashMap<u128, Vec<Hashed>> = HashMap::new();
+            for mut entry in group.drain(..) {
+                if let Some(full) = entry.full() {
+                    by_full.entry(full).or_default().push(entry);
+                }
+            }
+
+            for (_full, matches) in by_full {
+                if matches.len() < 2 {
+                    continue;
+                }
+                // Emit every pair within the confirmed-identical group.
+                for pair in matches.iter().combinations(2) {
+                    let (a, b) = (pair[0], pair[1]);
+                    debug!(path_a = debug(a.path), path_b = debug(b.path), "content duplicate");
+                    duplicates.push(Match {
+                        score: 1.0,
+                        path_a: a.path.clone(),
+                        path_b: b.path.clone(),
+                        total_size: a.size + b.size,
+                        tokens: Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    duplicates
+}
+
+/// Walk every configured root and collect the scanned entries.
+pub fn scan(config: &ScanConfig) -> Vec<Entry> {
+    let globs = build_globset(&config.ignore);
+    config
+        .roots
+        .iter()
+        .flat_map(|r| {
+            scan_dir(r, &config.filename_pattern, config.trie_len, &globs, config.use_gitignore)
+                .into_iter()
+        })
+        .collect()
+}
+
+fn order(mut matches: Vec<Match>, reverse: bool) -> Vec<Match> {
+    matches.sort_by_key(|m| m.total_size);
+    matches.sort_by_key(|m| (m.score * 10000.0) as u64);
+    if reverse {
+        matches.reverse();
+    }
+    matches
+}
+
+/// Scan the configured roots and return file pairs whose tokenised names are
+/// similar above the configured threshold, ordered for display.
+pub fn find_similar(config: &ScanConfig) -> Vec<Match> {
+    let entries = scan(config);
+    let limit = config.limit.as_deref().map(Limit::new);
+    order(
+        calculate_duplicates(&entries, config.threshold, limit.as_ref()),
+        config.reverse,
+    )
+}
+
+/// Scan the configured roots and return groups of byte-identical files.
+pub fn find_content_duplicates(config: &ScanConfig) -> Vec<Match> {
+    let entries = scan(config);
+    let limit = config.limit.as_deref().map(Limit::new);
+    let matches = calculate_content_duplicates(&entries)
+        .into_iter()
+        .filter(|m| {
+            limit
+                .as_ref()
+                .is_none_or(|l| l.matches(&m.path_a) || l.matches(&m.path_b))
+        })
+        .collect();
+    order(matches, config.reverse)
+}
+
+/// A file whose declared extension disagrees with its inferred content type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mismatch {
+    pub path: PathBuf,
+    /// The file's current extension, if it has one.
+    pub extension: Option<String>,
+    /// The extension inferred from the file's leading bytes.
+    pub detected: String,
+}
+
+/// Extension pairs that are interchangeable and should never be reported.
+pub fn default_extension_aliases() -> Vec<(String, String)> {
+    [
+        ("jpg", "jpeg"),
+        ("jpg", "jfif"),
+        ("tif", "tiff"),
+        ("htm", "html"),
+        ("m4v", "mp4"),
+        ("odt", "ott"),
+    ]
+    .iter()
+    .map(|(a, b)| (a.to_string(), b.to_string()))
+    .collect()
+}
+
+/// Parse a user allow-list file: one interchangeable pair per line, the two
+/// extensions separated by whitespace, a comma or a slash. Blank lines and
+/// lines starting with `#` are ignored.
+pub fn load_extension_aliases(path: &str) -> std::io::Result<Vec<(String, String)>> {
+    let contents = std::fs::read_to_string(path)?;
+    let aliases = contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| {
+            let mut parts = l
+                .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+                .filter(|p| !p.is_empty());
+            match (parts.next(), parts.next()) {
+                (Some(a), Some(b)) if !a.is_empty() && !b.is_empty() => {
+                    Some((a.to_lowercase(), b.to_lowercase()))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    Ok(aliases)
+}
+
+/// Whether `actual` and `detected` are a known-interchangeable pair.
+fn is_alias(actual: &str, detected: &str, aliases: &[(String, String)]) -> bool {
+    aliases
+        .iter()
+        .any(|(a, b)| (a == actual && b == detected) || (a == detected && b == actual))
+}
+
+/// Flag files whose declared extension disagrees with the type inferred from
+/// their leading bytes, skipping pairs present in `aliases`.
+#[instrument(skip(aliases))]
+pub fn check_extensions(config: &ScanConfig, aliases: &[(String, String)]) -> Vec<Mismatch> {
+    let entries = scan(config);
+    info!("Checking extensions of {} entries", entries.len());
+
+    let mut mismatches = Vec::new();
+    for (path, _, _) in &entries {
+        // Can only judge files whose content type we can actually infer.
+        let detected = match infer::get_from_path(path) {
+            Ok(Some(kind)) => kind.extension().to_lowercase(),
+            _ => continue,
+        };
+
+        let actual = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase());
+
+        let agrees = actual.as_deref() == Some(detected.as_str())
+            || actual
+                .as_deref()
+                .is_some_and(|a| is_alias(a, &detected, aliases));
+
+        if !agrees {
+            debug!(path = debug(path), ?actual, detected, "extension mismatch");
+            mismatches.push(Mismatch {
+                path: path.clone(),
+                extension: actual,
+                detected,
+            });
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_parse_with_mixed_separators() {
+        let dir = std::env::temp_dir().join("name-similarity-aliases-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("aliases.txt");
+        std::fs::write(
+            &path,
+            "# a comment\njpg jpeg\nsvg,svgz\nm4v/mp4\n\n   \nbad-single-token\n",
+        )
+        .unwrap();
+
+        let aliases = load_extension_aliases(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            aliases,
+            vec![
+                ("jpg".to_string(), "jpeg".to_string()),
+                ("svg".to_string(), "svgz".to_string()),
+                ("m4v".to_string(), "mp4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn is_alias_is_order_independent() {
+        let aliases = default_extension_aliases();
+        assert!(is_alias("jpg", "jpeg", &aliases));
+        assert!(is_alias("jpeg", "jpg", &aliases));
+        assert!(!is_alias("png", "jpeg", &aliases));
+    }
+
+    #[test]
+    fn limit_file_matches_only_that_file() {
+        let dir = std::env::temp_dir().join("name-similarity-limit-file-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target.txt");
+        let other = dir.join("other.txt");
+        std::fs::write(&target, "a").unwrap();
+        std::fs::write(&other, "b").unwrap();
+
+        let limit = Limit::new(&target);
+        assert!(limit.matches(&target));
+        assert!(!limit.matches(&other));
+    }
+
+    #[test]
+    fn limit_dir_matches_contained_paths() {
+        let dir = std::env::temp_dir().join("name-similarity-limit-dir-test");
+        let inside = dir.join("sub");
+        std::fs::create_dir_all(&inside).unwrap();
+        let within = inside.join("within.txt");
+        std::fs::write(&within, "a").unwrap();
+        let outside = std::env::temp_dir().join("name-similarity-limit-dir-outside.txt");
+        std::fs::write(&outside, "b").unwrap();
+
+        let limit = Limit::new(&dir);
+        assert!(limit.matches(&within));
+        assert!(!limit.matches(&outside));
+    }
+}